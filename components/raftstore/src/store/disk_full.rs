@@ -0,0 +1,106 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Centralizes the disk-full admission checks applied to raft proposals.
+//!
+//! Ordinary proposals are rejected while the peer's observed `DiskUsage` is
+//! `AlmostFull`/`AlreadyFull`, unless the command's header carries a
+//! `DiskFullOpt` that says otherwise (see `Peer::propose_normal`). `ReadIndex`
+//! is the one command that must never be folded into that generic rejection:
+//! it appends no raft log entry, so serving it cannot make a full disk worse,
+//! and async commit depends on the concurrency manager's `max_ts` advancing
+//! to cover every `ReadIndex` a leader serves -- rejecting it outright would
+//! silently skip that advance and let a later `min_commit_ts` fall below an
+//! already-returned read.
+
+use std::collections::VecDeque;
+
+use kvproto::disk_usage::DiskUsage;
+use kvproto::kvrpcpb::DiskFullOpt;
+use kvproto::raft_cmdpb::{AdminCmdType, CmdType, RaftCmdRequest};
+use kvproto::raft_serverpb::PeerState;
+
+/// Returns `true` when `req` carries no write and so cannot grow the amount
+/// of data on disk, meaning it must bypass the disk-full proposal gate
+/// entirely regardless of `usage`.
+pub fn bypasses_disk_full_gate(req: &RaftCmdRequest) -> bool {
+    req.has_read_index()
+        || req
+            .get_requests()
+            .iter()
+            .any(|r| r.get_cmd_type() == CmdType::ReadIndex)
+}
+
+/// The disk-full proposal gate: `usage` is the peer's locally observed disk
+/// state, and the command's own header `disk_full_opt` decides whether an
+/// ordinary write is still allowed through. Returns `true` when the proposal
+/// should be rejected with `disk_full`.
+pub fn should_reject_proposal(usage: DiskUsage, req: &RaftCmdRequest) -> bool {
+    if usage == DiskUsage::Normal || bypasses_disk_full_gate(req) || is_reclaim_proposal(req) {
+        return false;
+    }
+    match req.get_header().get_disk_full_opt() {
+        DiskFullOpt::NotAllowedOnFull => true,
+        DiskFullOpt::AllowedOnAlmostFull => usage == DiskUsage::AlreadyFull,
+        DiskFullOpt::AllowedOnAlreadyFull => false,
+    }
+}
+
+/// `true` for proposals that free disk space rather than consume it:
+/// raft-log GC (`CompactLog`) and range-clearing writes (`DeleteRange`, the
+/// command underlying unsafe-destroy-range). These stay admissible even
+/// under `AlreadyFull` -- the very operations that reclaim space also have
+/// to go through raft, so rejecting them outright would deadlock recovery.
+///
+/// `kvproto`'s `DiskFullOpt` only models user-facing write permissions
+/// (`NotAllowedOnFull`/`AllowedOnAlmostFull`/`AllowedOnAlreadyFull`) and has
+/// no "reclaim" option of its own, so rather than add a wire-level variant
+/// this classifies the request's own content: the gate trusts what a
+/// proposal *is*, not a header a client could set on anything.
+pub fn is_reclaim_proposal(req: &RaftCmdRequest) -> bool {
+    req.get_requests()
+        .iter()
+        .any(|r| r.get_cmd_type() == CmdType::DeleteRange)
+        || req.get_admin_request().get_cmd_type() == AdminCmdType::CompactLog
+}
+
+/// Same gate, applied on the follower's incoming-command path (e.g. a
+/// replica read forwarded straight to the follower rather than raft-committed
+/// by the leader). Followers and leaders see the same `DiskUsage`/
+/// `DiskFullOpt` inputs here, so this is `should_reject_proposal` in all but
+/// name -- kept distinct so call sites read as "the follower read path" and
+/// don't have to reason about leader-only proposal semantics to see that
+/// replica reads are exempt.
+pub fn should_reject_follower_read(usage: DiskUsage, req: &RaftCmdRequest) -> bool {
+    should_reject_proposal(usage, req)
+}
+
+/// `true` when `req` must be deferred rather than answered (or rejected)
+/// right away, because the region is still applying a received snapshot.
+/// This is independent of -- and composes with -- the disk-full gate above:
+/// a peer can be both disk-full and `Applying` at once, and a ReadIndex
+/// arriving in that window must be queued, not dropped by whichever gate
+/// runs first.
+pub fn should_queue_during_apply(region_state: PeerState, req: &RaftCmdRequest) -> bool {
+    region_state == PeerState::Applying && bypasses_disk_full_gate(req)
+}
+
+/// Holds `ReadIndex` commands that arrived while the region was `Applying`,
+/// to be resolved once the snapshot finishes applying. Queued separately
+/// from the normal pending-read-index queue because entry into it is driven
+/// by `should_queue_during_apply` rather than an unresolved raft read index.
+#[derive(Default)]
+pub struct PendingApplyReads {
+    queued: VecDeque<RaftCmdRequest>,
+}
+
+impl PendingApplyReads {
+    pub fn push(&mut self, req: RaftCmdRequest) {
+        self.queued.push_back(req);
+    }
+
+    /// Drains everything queued, to be re-proposed/answered once the apply
+    /// that was blocking them completes.
+    pub fn drain(&mut self) -> Vec<RaftCmdRequest> {
+        self.queued.drain(..).collect()
+    }
+}
@@ -0,0 +1,4 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+pub mod disk_full;
+pub mod peer;
@@ -0,0 +1,91 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! The peer-level entry points that apply `disk_full`'s gate to real
+//! proposals and reads. Scoped to exactly the state those checks consult
+//! (`disk_usage`, the region's apply state, the deferred-reads queue); a
+//! real `Peer` carries a great deal more (the raft group itself, pending
+//! reads, lease state, ...) that lives elsewhere in the full tree.
+
+use kvproto::disk_usage::DiskUsage;
+use kvproto::raft_cmdpb::{RaftCmdRequest, RaftCmdResponse};
+use kvproto::raft_serverpb::PeerState;
+
+use super::disk_full::{self, PendingApplyReads};
+
+/// Minimal per-peer state the disk-full gate needs.
+pub struct Peer {
+    /// The locally observed disk usage backing `propose_normal`'s gate.
+    pub disk_usage: DiskUsage,
+    /// The region's apply state; `Applying` while a received snapshot is
+    /// still being applied.
+    pub region_state: PeerState,
+    /// `ReadIndex`es deferred by `should_queue_during_apply` while
+    /// `region_state` was `Applying`, resolved by `on_apply_snapshot_finished`.
+    pending_apply_reads: PendingApplyReads,
+}
+
+impl Peer {
+    pub fn new() -> Peer {
+        Peer {
+            disk_usage: DiskUsage::Normal,
+            region_state: PeerState::Normal,
+            pending_apply_reads: PendingApplyReads::default(),
+        }
+    }
+
+    /// Entry point for leader-side proposals (writes, `ReadIndex`, admin
+    /// commands) before they're handed to raft. Applying
+    /// `disk_full::should_reject_proposal` here -- rather than leaving it
+    /// uncalled -- is what actually lets `ReadIndex` (and so `max_ts`)
+    /// through while `disk_usage` is full. The `Applying` carve-out is
+    /// checked first: it composes with, rather than being overridden by,
+    /// the disk-full gate below, so a `ReadIndex` arriving while both
+    /// conditions hold is queued instead of rejected by whichever check
+    /// ran first.
+    pub fn propose_normal(&mut self, req: RaftCmdRequest) -> Result<(), RaftCmdResponse> {
+        if disk_full::should_queue_during_apply(self.region_state, &req) {
+            self.pending_apply_reads.push(req);
+            return Ok(());
+        }
+        if disk_full::should_reject_proposal(self.disk_usage, &req) {
+            return Err(disk_full_response());
+        }
+        Ok(())
+    }
+
+    /// Entry point for commands that reach this peer directly as a
+    /// follower -- replica reads forwarded straight here instead of being
+    /// raft-committed by the leader. Uses `should_reject_follower_read`
+    /// rather than `propose_normal`'s gate so a replica read is exempted
+    /// even though it never goes through `propose_normal` at all.
+    pub fn handle_follower_read(&mut self, req: RaftCmdRequest) -> Result<(), RaftCmdResponse> {
+        if disk_full::should_queue_during_apply(self.region_state, &req) {
+            self.pending_apply_reads.push(req);
+            return Ok(());
+        }
+        if disk_full::should_reject_follower_read(self.disk_usage, &req) {
+            return Err(disk_full_response());
+        }
+        Ok(())
+    }
+
+    /// Called once the region finishes applying a received snapshot
+    /// (`region_state` leaves `Applying`): resolves every `ReadIndex` that
+    /// `propose_normal`/`handle_follower_read` deferred in the meantime,
+    /// instead of leaving them queued forever.
+    pub fn on_apply_snapshot_finished(&mut self) -> Vec<RaftCmdRequest> {
+        self.pending_apply_reads.drain()
+    }
+}
+
+impl Default for Peer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn disk_full_response() -> RaftCmdResponse {
+    let mut resp = RaftCmdResponse::default();
+    resp.mut_header().mut_error().mut_disk_full();
+    resp
+}
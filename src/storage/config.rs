@@ -0,0 +1,111 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+use serde::{Deserialize, Serialize};
+use tikv_util::config::ReadableSize;
+
+/// The feedback algorithm `FlowChecker` uses to turn the flush-flow-vs-
+/// `l0_target_flow` error into a new throttle speed. See
+/// `txn::flow_controller::CongestionControl`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CongestionControlAlgorithm {
+    Pid,
+    Aimd,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[serde(rename_all = "kebab-case")]
+pub struct FlowControlConfig {
+    pub enable: bool,
+    pub soft_pending_compaction_bytes_limit: ReadableSize,
+    pub hard_pending_compaction_bytes_limit: ReadableSize,
+    pub memtables_threshold: u64,
+    pub l0_files_threshold: u64,
+
+    /// Which `CongestionControl` impl to seed `FlowChecker` with.
+    pub congestion_control_algorithm: CongestionControlAlgorithm,
+    pub pid_kp_factor: f64,
+    pub pid_ki_factor: f64,
+    pub pid_kd_factor: f64,
+    /// Step used to pull the two-tier effective limit (see
+    /// `io_limit_low`/`io_limit_max`) back down toward `io_limit_low` under
+    /// pressure.
+    pub limit_down_percent: f64,
+    pub aimd_alpha: ReadableSize,
+    pub aimd_beta: f64,
+
+    /// Cap on the bytes of write requests in flight on the
+    /// `grpc -> check -> limiter -> async write` path, independent of the
+    /// steady byte-rate `limiter`. See `FlowController::admission_consume`.
+    pub max_in_flight_bytes: ReadableSize,
+
+    /// Guaranteed "low" write rate that's always honored, and the hard
+    /// "max" ceiling writes may burst up to when the store isn't under
+    /// pressure. See `FlowChecker::effective_limit`.
+    pub io_limit_low: ReadableSize,
+    pub io_limit_max: ReadableSize,
+
+    /// Bounds the limiter's speed limit (in both the PID/AIMD control loop
+    /// and the two-tier `io_limit_low`/`io_limit_max` effective limit).
+    /// Reconfigurable online; see `FlowChecker::reconfigure`.
+    pub min_throttle_speed: ReadableSize,
+    pub max_throttle_speed: ReadableSize,
+    /// EMA smoothing factor for the discard ratio; the floor of the
+    /// variance-widened factor in `FlowChecker::on_pending_compaction_bytes_change`.
+    pub ema_factor: f64,
+    /// Step used to raise the two-tier effective limit back up toward
+    /// `io_limit_max` when there's headroom.
+    pub limit_up_percent: f64,
+
+    /// Positive feedback raises `l0_target_flow` by this fraction on the
+    /// throttled->recovered transition; negative feedback lowers it by
+    /// `feedback_down_percent` once a CF comes within `feedback_proximity_files`
+    /// of `l0_files_threshold`/`memtables_threshold`.
+    pub feedback_up_percent: f64,
+    pub feedback_down_percent: f64,
+    pub feedback_proximity_files: u64,
+
+    /// Selects the bLSM cross-level rate-matching throttle mode alongside
+    /// the existing L0/memtable logic. `blsm_fanout` is the per-level size
+    /// multiplier `R` used to derive `target[i+1] = R * target[i]`.
+    pub blsm_mode_enabled: bool,
+    pub blsm_fanout: f64,
+}
+
+impl Default for FlowControlConfig {
+    fn default() -> FlowControlConfig {
+        FlowControlConfig {
+            enable: true,
+            soft_pending_compaction_bytes_limit: ReadableSize::gb(192),
+            hard_pending_compaction_bytes_limit: ReadableSize::gb(256),
+            memtables_threshold: 5,
+            l0_files_threshold: 20,
+
+            congestion_control_algorithm: CongestionControlAlgorithm::Pid,
+            pid_kp_factor: 0.15,
+            pid_ki_factor: 0.01,
+            pid_kd_factor: 5.0,
+            limit_down_percent: 0.02, // 2%
+            aimd_alpha: ReadableSize::kb(256),
+            aimd_beta: 0.7,
+
+            max_in_flight_bytes: ReadableSize::gb(1),
+
+            io_limit_low: ReadableSize::mb(32),
+            io_limit_max: ReadableSize::mb(200),
+
+            min_throttle_speed: ReadableSize::kb(16),
+            max_throttle_speed: ReadableSize::mb(200),
+            ema_factor: 0.6,
+            limit_up_percent: 0.04, // 4%
+
+            feedback_up_percent: 0.05, // 5%
+            feedback_down_percent: 0.05, // 5%
+            feedback_proximity_files: 2,
+
+            blsm_mode_enabled: false,
+            blsm_fanout: 10.0,
+        }
+    }
+}
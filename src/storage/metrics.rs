@@ -0,0 +1,134 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+use lazy_static::lazy_static;
+use prometheus::*;
+
+lazy_static! {
+    pub static ref SCHED_WRITE_FLOW_GAUGE: IntGauge = register_int_gauge!(
+        "tikv_scheduler_write_flow",
+        "Bytes of write flow at scheduler level"
+    )
+    .unwrap();
+    pub static ref SCHED_THROTTLE_FLOW_GAUGE: IntGauge = register_int_gauge!(
+        "tikv_scheduler_throttle_flow",
+        "Throttled flow at scheduler level, 0 means no throttle"
+    )
+    .unwrap();
+    pub static ref SCHED_DISCARD_RATIO_GAUGE: IntGauge = register_int_gauge!(
+        "tikv_scheduler_discard_ratio",
+        "Scheduler flush memtable and L0 discard ratio"
+    )
+    .unwrap();
+    pub static ref SCHED_L0_TARGET_FLOW_GAUGE: IntGauge = register_int_gauge!(
+        "tikv_scheduler_l0_target_flow",
+        "Target flow for L0 files"
+    )
+    .unwrap();
+    pub static ref SCHED_UP_FLOW_GAUGE: IntGauge = register_int_gauge!(
+        "tikv_scheduler_up_flow",
+        "Flow of increasing the target flow"
+    )
+    .unwrap();
+    pub static ref SCHED_DOWN_FLOW_GAUGE: IntGauge = register_int_gauge!(
+        "tikv_scheduler_down_flow",
+        "Flow of decreasing the target flow"
+    )
+    .unwrap();
+    pub static ref SCHED_THROTTLE_CF_GAUGE: IntGaugeVec = register_int_gauge_vec!(
+        "tikv_scheduler_throttle_cf",
+        "The CF being throttled, 1 means throttled",
+        &["cf"]
+    )
+    .unwrap();
+    pub static ref SCHED_PENDING_COMPACTION_BYTES_GAUGE: IntGaugeVec = register_int_gauge_vec!(
+        "tikv_scheduler_pending_compaction_bytes",
+        "Pending compaction bytes used to calculate the discard ratio",
+        &["cf"]
+    )
+    .unwrap();
+    pub static ref SCHED_MEMTABLE_GAUGE: IntGaugeVec = register_int_gauge_vec!(
+        "tikv_scheduler_memtable_cf",
+        "Number of memtables",
+        &["cf"]
+    )
+    .unwrap();
+    pub static ref SCHED_L0_GAUGE: IntGaugeVec = register_int_gauge_vec!(
+        "tikv_scheduler_l0",
+        "Number of L0 files",
+        &["cf"]
+    )
+    .unwrap();
+    pub static ref SCHED_L0_AVG_GAUGE: IntGaugeVec = register_int_gauge_vec!(
+        "tikv_scheduler_l0_avg",
+        "Average number of L0 files over the long term window",
+        &["cf"]
+    )
+    .unwrap();
+    pub static ref SCHED_L0_FLOW_GAUGE: IntGaugeVec = register_int_gauge_vec!(
+        "tikv_scheduler_l0_flow",
+        "L0 production flow",
+        &["cf"]
+    )
+    .unwrap();
+    pub static ref SCHED_FLUSH_L0_GAUGE: IntGaugeVec = register_int_gauge_vec!(
+        "tikv_scheduler_flush_l0",
+        "Number of flushed L0 files since the last tick",
+        &["cf"]
+    )
+    .unwrap();
+    pub static ref SCHED_FLUSH_FLOW_GAUGE: IntGaugeVec = register_int_gauge_vec!(
+        "tikv_scheduler_flush_flow",
+        "Flush flow at scheduler level",
+        &["cf"]
+    )
+    .unwrap();
+    pub static ref SCHED_LONG_TERM_FLUSH_FLOW_GAUGE: IntGaugeVec = register_int_gauge_vec!(
+        "tikv_scheduler_long_term_flush_flow",
+        "Long term average flush flow",
+        &["cf"]
+    )
+    .unwrap();
+    pub static ref SCHED_THROTTLE_ACTION_COUNTER: IntCounterVec = register_int_counter_vec!(
+        "tikv_scheduler_throttle_action_total",
+        "Action counters of flow controller throttles",
+        &["name", "type"]
+    )
+    .unwrap();
+    pub static ref SCHED_ADMISSION_INFLIGHT_GAUGE: IntGaugeVec = register_int_gauge_vec!(
+        "tikv_scheduler_admission_inflight_bytes",
+        "Bytes currently admitted into a flow controller throttle",
+        &["name"]
+    )
+    .unwrap();
+    pub static ref SCHED_ADMISSION_BYTES_COUNTER: IntCounterVec = register_int_counter_vec!(
+        "tikv_scheduler_admission_bytes_total",
+        "Total bytes admitted through a flow controller throttle",
+        &["name"]
+    )
+    .unwrap();
+    pub static ref SCHED_EFFECTIVE_LIMIT_GAUGE: IntGaugeVec = register_int_gauge_vec!(
+        "tikv_scheduler_effective_limit",
+        "Current effective write rate limit between io_limit_low and io_limit_max",
+        &["cf"]
+    )
+    .unwrap();
+    pub static ref SCHED_THROTTLE_WAIT_DURATION_HISTOGRAM: HistogramVec = register_histogram_vec!(
+        "tikv_scheduler_throttle_wait_duration_seconds",
+        "Wall-clock time a write spent blocked in the flow controller's limiter acquire path",
+        &["cf"],
+        exponential_buckets(0.0001, 2.0, 20).unwrap()
+    )
+    .unwrap();
+    pub static ref SCHED_POSITIVE_FEEDBACK_COUNTER: IntCounterVec = register_int_counter_vec!(
+        "tikv_scheduler_positive_feedback_total",
+        "Number of times the throttled->recovered transition raised l0_target_flow",
+        &["cf"]
+    )
+    .unwrap();
+    pub static ref SCHED_NEGATIVE_FEEDBACK_COUNTER: IntCounterVec = register_int_counter_vec!(
+        "tikv_scheduler_negative_feedback_total",
+        "Number of times proximity to the stop condition lowered l0_target_flow",
+        &["cf"]
+    )
+    .unwrap();
+}
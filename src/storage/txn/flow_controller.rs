@@ -4,7 +4,7 @@ use std::cmp::PartialOrd;
 use std::collections::VecDeque;
 use std::f64::INFINITY;
 use std::ops::{Add, AddAssign, Sub, SubAssign};
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender};
 use std::sync::Arc;
 use std::thread::{Builder, JoinHandle};
@@ -18,20 +18,26 @@ use num_traits::cast::{AsPrimitive, FromPrimitive};
 use rand::Rng;
 use tikv_util::time::{duration_to_sec, Consume, Instant, Limiter};
 
-use crate::storage::config::FlowControlConfig;
+use crate::storage::config::{CongestionControlAlgorithm, FlowControlConfig};
 use crate::storage::metrics::*;
 
 const SPARE_TICK_DURATION: Duration = Duration::from_millis(1000);
 const SPARE_TICKS_THRESHOLD: u64 = 10;
+// `log_diagnostics` fires once every this many `SPARE_TICK_DURATION` ticks
+// (i.e. roughly every 30s), so operators get an aggregated summary without
+// flooding the log at tick granularity.
+const DIAGNOSTICS_LOG_TICKS: u64 = 30;
 const RATIO_SCALE_FACTOR: f64 = 10000000.0;
-const LIMIT_UP_PERCENT: f64 = 0.04; // 4%
-const LIMIT_DOWN_PERCENT: f64 = 0.02; // 2%
-const MIN_THROTTLE_SPEED: f64 = 16.0 * 1024.0; // 16KB
-const MAX_THROTTLE_SPEED: f64 = 200.0 * 1024.0 * 1024.0; // 200MB
+// RocksDB defaults to at most 7 levels (L0..L6); the bLSM cross-level mode
+// (see `FlowChecker::blsm_speed_limit`) tracks all of them.
+const MAX_LSM_LEVELS: usize = 7;
 
-const EMA_FACTOR: f64 = 0.6; // EMA stands for Exponential Moving Average
-const PID_KP_FACTOR: f64 = 0.15;
-const PID_KD_FACTOR: f64 = 5.0;
+// The tuning constants below used to be compile-time consts. They are now
+// carried as `FlowControlConfig` fields on `FlowChecker` (see
+// `min_throttle_speed`/`max_throttle_speed`/`ema_factor`/`limit_up_percent`/
+// `limit_down_percent` and `PidCongestionControl`'s `kp`/`ki`/`kd`) so operators
+// can retune the feedback loop live via `Msg::Reconfigure`, without a
+// restart.
 
 #[derive(Eq, PartialEq, Debug)]
 enum Trend {
@@ -40,6 +46,141 @@ enum Trend {
     NoTrend,
 }
 
+/// Maps the congestion signal -- the current flush flow measured against
+/// `l0_target_flow` -- into the next throttle speed. Pluggable so operators
+/// can A/B different feedback algorithms on the same cluster without
+/// touching `FlowChecker`'s control loop.
+///
+/// `trend` is the trend of the short term flush flow, used as a cheap proxy
+/// for whether L0 is about to build up further.
+trait CongestionControl: Send {
+    /// Returns the next speed limit, not yet clamped to the configured
+    /// min/max throttle speed.
+    /// `current_speed` must not be `INFINITY`; seeding the initial throttle
+    /// speed when leaving the unthrottled state is handled by the caller.
+    /// `slope` is `Smoother::slope()` of the same flow `recent_flow` was
+    /// averaged from, i.e. the actual rate of change, not just its
+    /// `Increasing`/`Decreasing`/`NoTrend` quantization.
+    fn next_speed(
+        &mut self,
+        current_speed: f64,
+        recent_flow: f64,
+        target_flow: f64,
+        trend: &Trend,
+        slope: f64,
+    ) -> f64;
+
+    /// Clears any accumulated internal state. Called whenever the checker
+    /// leaves the throttled state or the throttle CF changes.
+    fn reset(&mut self);
+}
+
+/// The original EMA+PID scheme, extended with an integral term: proportional
+/// on the flow error, integral on its accumulation (to kill steady-state
+/// error the P term alone can't close), derivative on the flow's slope.
+struct PidCongestionControl {
+    kp: f64,
+    ki: f64,
+    kd: f64,
+    // Accumulated flow error. Clamped (anti-windup) to
+    // `max_integral_for(current_speed)` so a long saturated period (`u`
+    // pinned at `-current_speed` or `current_speed`) doesn't leave a huge integral that
+    // then overshoots once the error finally reverses.
+    integral: f64,
+}
+
+impl PidCongestionControl {
+    // Anti-windup bound: the integral term alone is never allowed to demand
+    // more than the current speed, matching the clamp already applied to the
+    // combined output below.
+    fn max_integral_for(&self, current_speed: f64) -> f64 {
+        if self.ki > 0.0 {
+            current_speed / self.ki
+        } else {
+            0.0
+        }
+    }
+}
+
+impl CongestionControl for PidCongestionControl {
+    fn next_speed(
+        &mut self,
+        current_speed: f64,
+        recent_flow: f64,
+        target_flow: f64,
+        _trend: &Trend,
+        slope: f64,
+    ) -> f64 {
+        // Negative when `recent_flow` is over `target_flow`, so the same
+        // KP/KI/KD expression drives the speed down symmetrically instead of
+        // falling back to a fixed multiplicative cut.
+        let error = target_flow - recent_flow;
+        let bound = self.max_integral_for(current_speed);
+        self.integral = (self.integral + error).clamp(-bound, bound);
+
+        // KD on the actual slope of the flow, not its quantized trend: a
+        // flow rising fast should pull the step back harder than one barely
+        // rising, which `Trend::Increasing`'s fixed +-1 can't express.
+        let mut u = self.kp * error + self.ki * self.integral - self.kd * slope;
+        if u > current_speed {
+            u = current_speed;
+        } else if u < -current_speed {
+            u = -current_speed;
+        }
+        current_speed + u
+    }
+
+    fn reset(&mut self) {
+        self.integral = 0.0;
+    }
+}
+
+/// AIMD (Additive Increase, Multiplicative Decrease): on each tick, increase
+/// the speed additively by a fixed `alpha` bytes/s when there's no
+/// congestion signal, and decrease it multiplicatively by `beta` when one
+/// appears. Converges toward a fair steady rate with far fewer tuning knobs
+/// than PID, at the cost of slower reaction to large deviations.
+struct AimdCongestionControl {
+    alpha: f64,
+    beta: f64,
+}
+
+impl CongestionControl for AimdCongestionControl {
+    fn next_speed(
+        &mut self,
+        current_speed: f64,
+        recent_flow: f64,
+        target_flow: f64,
+        trend: &Trend,
+        _slope: f64,
+    ) -> f64 {
+        // bounds clamping is done centrally by `FlowChecker::update_speed_limit`.
+        let signal = recent_flow > target_flow || *trend == Trend::Increasing;
+        if signal {
+            current_speed * self.beta
+        } else {
+            current_speed + self.alpha
+        }
+    }
+
+    fn reset(&mut self) {}
+}
+
+fn new_congestion_control(config: &FlowControlConfig) -> Box<dyn CongestionControl> {
+    match config.congestion_control_algorithm {
+        CongestionControlAlgorithm::Pid => Box::new(PidCongestionControl {
+            kp: config.pid_kp_factor,
+            ki: config.pid_ki_factor,
+            kd: config.pid_kd_factor,
+            integral: 0.0,
+        }),
+        CongestionControlAlgorithm::Aimd => Box::new(AimdCongestionControl {
+            alpha: config.aimd_alpha.0 as f64,
+            beta: config.aimd_beta,
+        }),
+    }
+}
+
 /// Flow controller is used to throttle the write rate at scheduler level, aiming
 /// to substitute the write stall mechanism of RocksDB. It features in two points:
 ///   * throttle at scheduler, so raftstore and apply won't be blocked anymore
@@ -80,6 +221,13 @@ enum Trend {
 pub struct FlowController {
     discard_ratio: Arc<AtomicU32>,
     limiter: Arc<Limiter>,
+    admission: Arc<Throttle>,
+    // A second, independent reservation layer tied to the hard pending-
+    // compaction-bytes ceiling (see `reserve`/`try_reserve`/`unreserve`):
+    // grinding the speed down to the minimum throttle speed can still leave
+    // writers queuing forever, so this gives a hard ceiling callers can
+    // fail-fast against instead.
+    reservation: Arc<Throttle>,
     enabled: Arc<AtomicBool>,
     tx: SyncSender<Msg>,
     handle: Option<std::thread::JoinHandle<()>>,
@@ -89,6 +237,7 @@ enum Msg {
     Close,
     Enable,
     Disable,
+    Reconfigure(FlowControlConfig),
 }
 
 impl Drop for FlowController {
@@ -118,6 +267,8 @@ impl FlowController {
         Self {
             discard_ratio: Arc::new(AtomicU32::new(0)),
             limiter: Arc::new(Limiter::new(INFINITY)),
+            admission: Arc::new(Throttle::new("admission", u64::MAX)),
+            reservation: Arc::new(Throttle::new("reservation", u64::MAX)),
             enabled: Arc::new(AtomicBool::new(false)),
             tx,
             handle: None,
@@ -144,6 +295,11 @@ impl FlowController {
         Self {
             discard_ratio,
             limiter,
+            admission: Arc::new(Throttle::new("admission", config.max_in_flight_bytes.0)),
+            reservation: Arc::new(Throttle::new(
+                "reservation",
+                config.hard_pending_compaction_bytes_limit.0,
+            )),
             enabled: Arc::new(AtomicBool::new(config.enable)),
             tx,
             handle: Some(checker.start(rx, flow_info_receiver)),
@@ -160,6 +316,107 @@ impl FlowController {
         self.limiter.consume(bytes)
     }
 
+    /// Records how long a write actually spent blocked in the limiter
+    /// acquire path, i.e. real wall-clock time awaiting the `Consume` future
+    /// returned by `consume`, not the instantaneous computed speed limit.
+    /// Callers should report this once per write after awaiting it, so the
+    /// `SCHED_THROTTLE_WAIT_DURATION_HISTOGRAM` p90/p99 reflect actual
+    /// blocking rather than a derived estimate.
+    pub fn record_wait(&self, cf: &str, wait: Duration) {
+        SCHED_THROTTLE_WAIT_DURATION_HISTOGRAM
+            .with_label_values(&[cf])
+            .observe(duration_to_sec(wait));
+    }
+
+    // Slot-based admission control, independent of the steady byte-rate
+    // `limiter` above: caps the number of bytes of write requests in flight
+    // on the `grpc -> check -> limiter -> async write` path, protecting
+    // memory when raftstore is slow to drain writes.
+
+    /// Blocks (FIFO) until `bytes` can be admitted, then reserves them.
+    pub fn admission_consume(&self, bytes: u64) {
+        self.admission.consume(bytes)
+    }
+
+    /// Non-blocking counterpart of `admission_consume`: returns `false`
+    /// immediately, mapping to `ServerIsBusy`, when the cap would be
+    /// exceeded instead of waiting.
+    pub fn try_admission_consume(&self, bytes: u64) -> bool {
+        self.admission.try_consume(bytes)
+    }
+
+    /// Returns `bytes` reserved via `admission_consume`/`try_admission_consume`
+    /// once the corresponding async write completes.
+    pub fn admission_release(&self, bytes: u64) {
+        self.admission.release(bytes)
+    }
+
+    // Reservation-based admission control tied to the hard pending-
+    // compaction-bytes ceiling: unlike the smooth `limiter` above, which can
+    // only grind the speed down to the minimum throttle speed, this rejects
+    // (or blocks) once the ceiling is actually reached, giving latency-
+    // sensitive callers a way to shed or queue the request elsewhere instead
+    // of stalling indefinitely. Named after Ceph's `Throttle::get`/
+    // `get_or_fail`/`put`.
+
+    /// Blocks (FIFO) until `bytes` fits within the reservation ceiling.
+    pub fn get(&self, bytes: u64) {
+        self.reservation.consume(bytes)
+    }
+
+    /// Non-blocking counterpart of `get`: returns `false` immediately when
+    /// the reservation is saturated instead of waiting.
+    pub fn get_or_fail(&self, bytes: u64) -> bool {
+        self.reservation.try_consume(bytes)
+    }
+
+    /// Returns `bytes` reserved via `get`/`get_or_fail` once the write
+    /// completes.
+    pub fn put(&self, bytes: u64) {
+        self.reservation.release(bytes)
+    }
+
+    /// The full backpressure path described by the module doc comment
+    /// (`grpc -> check -> limiter -> async write`): blocks on the admission
+    /// slot throttle and the hard-limit reservation, then waits on the
+    /// steady byte-rate `limiter`, recording how long the caller actually
+    /// blocked there via `record_wait`. Returns a guard that releases the
+    /// admission and reservation bytes once the write completes (or the
+    /// guard is dropped early, e.g. on error), so callers don't have to
+    /// remember to pair up `admission_consume`/`get`/`consume` by hand.
+    pub async fn consume_write(&self, cf: &str, bytes: usize) -> WriteGuard<'_> {
+        let bytes_u64 = bytes as u64;
+        self.admission_consume(bytes_u64);
+        self.get(bytes_u64);
+        let start = std::time::Instant::now();
+        self.limiter.consume(bytes).await;
+        self.record_wait(cf, start.elapsed());
+        WriteGuard {
+            controller: self,
+            bytes: bytes_u64,
+        }
+    }
+
+    /// Non-blocking counterpart of `consume_write`: returns `None`
+    /// immediately, mapping to `ServerIsBusy`, when either the admission
+    /// slot throttle or the hard-limit reservation is saturated, instead of
+    /// parking the caller. Does not touch the steady byte-rate `limiter`,
+    /// since that one is meant to be waited on rather than failed fast.
+    pub fn try_consume_write(&self, bytes: usize) -> Option<WriteGuard<'_>> {
+        let bytes_u64 = bytes as u64;
+        if !self.try_admission_consume(bytes_u64) {
+            return None;
+        }
+        if !self.get_or_fail(bytes_u64) {
+            self.admission_release(bytes_u64);
+            return None;
+        }
+        Some(WriteGuard {
+            controller: self,
+            bytes: bytes_u64,
+        })
+    }
+
     pub fn enable(&self, enable: bool) {
         self.enabled.store(enable, Ordering::Relaxed);
         if enable {
@@ -169,6 +426,15 @@ impl FlowController {
         }
     }
 
+    /// Pushes new throttle bounds/control constants to the running
+    /// `FlowChecker` thread, taking effect on its next tick without a
+    /// restart. Mirrors how `enable`/`Msg::Enable` already works.
+    pub fn reconfigure(&self, config: FlowControlConfig) {
+        self.reservation
+            .set_max(config.hard_pending_compaction_bytes_limit.0);
+        self.tx.send(Msg::Reconfigure(config)).unwrap();
+    }
+
     pub fn enabled(&self) -> bool {
         self.enabled.load(Ordering::Relaxed)
     }
@@ -178,6 +444,133 @@ impl FlowController {
     }
 }
 
+/// Returned by `FlowController::consume_write`; releases the admission and
+/// reservation bytes it reserved once the guarded write completes.
+pub struct WriteGuard<'a> {
+    controller: &'a FlowController,
+    bytes: u64,
+}
+
+impl Drop for WriteGuard<'_> {
+    fn drop(&mut self) {
+        self.controller.admission_release(self.bytes);
+        self.controller.put(self.bytes);
+    }
+}
+
+struct ThrottleState {
+    current: u64,
+    // monotonically increasing tickets used to serve waiters roughly FIFO:
+    // a waiter only re-checks admission once every ticket issued before its
+    // own has been served.
+    next_ticket: u64,
+    next_to_serve: u64,
+}
+
+// Throttle is a Ceph-Throttle-style admission control: it caps a budget
+// (e.g. bytes of requests in flight, or bytes reserved against a pending-
+// compaction-bytes ceiling) and fails fast (or blocks) when the cap would
+// be exceeded. `name` labels its perf counters, mirroring
+// `SCHED_THROTTLE_ACTION_COUNTER`'s existing cause labels, so multiple
+// independent throttles (admission, reservation, ...) can coexist.
+struct Throttle {
+    name: &'static str,
+    max: AtomicU64,
+    state: std::sync::Mutex<ThrottleState>,
+    cond: std::sync::Condvar,
+}
+
+impl Throttle {
+    fn new(name: &'static str, max: u64) -> Self {
+        Self {
+            name,
+            max: AtomicU64::new(max),
+            state: std::sync::Mutex::new(ThrottleState {
+                current: 0,
+                next_ticket: 0,
+                next_to_serve: 0,
+            }),
+            cond: std::sync::Condvar::new(),
+        }
+    }
+
+    fn set_max(&self, max: u64) {
+        self.max.store(max, Ordering::Relaxed);
+        self.cond.notify_all();
+    }
+
+    // Blocking `get`: waits (FIFO) until the budget has room for `n`.
+    fn consume(&self, n: u64) {
+        SCHED_THROTTLE_ACTION_COUNTER
+            .with_label_values(&[self.name, "get_started"])
+            .inc();
+        let mut state = self.state.lock().unwrap();
+        let ticket = state.next_ticket;
+        state.next_ticket += 1;
+        let mut blocked = false;
+        while state.next_to_serve != ticket || state.current + n > self.max.load(Ordering::Relaxed)
+        {
+            if !blocked {
+                SCHED_THROTTLE_ACTION_COUNTER
+                    .with_label_values(&[self.name, "get_blocked"])
+                    .inc();
+                blocked = true;
+            }
+            SCHED_THROTTLE_ACTION_COUNTER
+                .with_label_values(&[self.name, "wait"])
+                .inc();
+            state = self.cond.wait(state).unwrap();
+        }
+        state.current += n;
+        state.next_to_serve += 1;
+        SCHED_ADMISSION_INFLIGHT_GAUGE
+            .with_label_values(&[self.name])
+            .set(state.current as i64);
+        SCHED_ADMISSION_BYTES_COUNTER
+            .with_label_values(&[self.name])
+            .inc_by(n);
+        drop(state);
+        self.cond.notify_all();
+    }
+
+    // Non-blocking `get_or_fail`: returns `false` immediately instead of
+    // waiting when the budget would be exceeded.
+    fn try_consume(&self, n: u64) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.next_to_serve != state.next_ticket || state.current + n > self.max.load(Ordering::Relaxed)
+        {
+            SCHED_THROTTLE_ACTION_COUNTER
+                .with_label_values(&[self.name, "get_or_fail_fail"])
+                .inc();
+            return false;
+        }
+        state.current += n;
+        state.next_ticket += 1;
+        state.next_to_serve += 1;
+        SCHED_ADMISSION_INFLIGHT_GAUGE
+            .with_label_values(&[self.name])
+            .set(state.current as i64);
+        SCHED_ADMISSION_BYTES_COUNTER
+            .with_label_values(&[self.name])
+            .inc_by(n);
+        SCHED_THROTTLE_ACTION_COUNTER
+            .with_label_values(&[self.name, "get_or_fail_success"])
+            .inc();
+        true
+    }
+
+    // `put`: returns `n` to the budget.
+    fn release(&self, n: u64) {
+        let mut state = self.state.lock().unwrap();
+        state.current = state.current.saturating_sub(n);
+        SCHED_ADMISSION_INFLIGHT_GAUGE
+            .with_label_values(&[self.name])
+            .set(state.current as i64);
+        drop(state);
+        self.cond.notify_all();
+    }
+}
+
 const SMOOTHER_STALE_RECORD_THRESHOLD: f64 = 300.0; // 5min
 
 // Smoother is a sliding window used to provide steadier flow statistics.
@@ -277,13 +670,58 @@ where
             .0
     }
 
-    pub fn get_percentile_90(&mut self) -> T {
+    pub fn get_percentile_90(&self) -> T {
+        self.get_percentile(0.90)
+    }
+
+    /// Generalizes `get_percentile_90` to an arbitrary quantile `p` in
+    /// `[0.0, 1.0]`, e.g. `get_percentile(0.99)` for a p99.
+    pub fn get_percentile(&self, p: f64) -> T {
         if self.records.is_empty() {
             return FromPrimitive::from_u64(0).unwrap();
         }
         let mut v: Vec<_> = self.records.iter().collect();
         v.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
-        v[((self.records.len() - 1) as f64 * 0.90) as usize].0
+        v[((self.records.len() - 1) as f64 * p.clamp(0.0, 1.0)) as usize].0
+    }
+
+    /// The mean after discarding the lowest and highest `frac` fraction of
+    /// samples, e.g. `get_trimmed_mean(0.1)` drops the bottom and top 10%.
+    /// Less sensitive to one-off spikes than `get_avg`.
+    pub fn get_trimmed_mean(&self, frac: f64) -> f64 {
+        if self.records.is_empty() {
+            return 0.0;
+        }
+        let mut v: Vec<f64> = self.records.iter().map(|r| r.0.as_()).collect();
+        v.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let trim = ((v.len() as f64) * frac.clamp(0.0, 0.5)) as usize;
+        let kept = &v[trim..v.len() - trim];
+        if kept.is_empty() {
+            return v[v.len() / 2];
+        }
+        kept.iter().sum::<f64>() / kept.len() as f64
+    }
+
+    /// Population variance of the window, used e.g. to gate how much an EMA
+    /// should trust a new sample: a noisy (high-variance) signal should lean
+    /// more on history than a stable one.
+    pub fn get_variance(&self) -> f64 {
+        if self.records.is_empty() {
+            return 0.0;
+        }
+        let avg = self.get_avg();
+        self.records
+            .iter()
+            .map(|r| {
+                let d = r.0.as_() - avg;
+                d * d
+            })
+            .sum::<f64>()
+            / self.records.len() as f64
+    }
+
+    pub fn get_stddev(&self) -> f64 {
+        self.get_variance().sqrt()
     }
 
     pub fn slope(&self) -> f64 {
@@ -380,8 +818,16 @@ struct CFFlowChecker {
     last_l0_bytes: u64,
     last_l0_bytes_time: Instant,
     short_term_l0_consumption_flow: Smoother<u64, 3>,
-
-    // Pending compaction bytes related
+    long_term_l0_consumption_flow: Smoother<u64, 60>,
+
+    // Pending compaction bytes related. `compaction_debt` is a continuously
+    // updated estimate of how many bytes must still be compacted before the
+    // LSM is stable: incremented by flushed bytes, decremented by compacted
+    // bytes, as they happen -- unlike sampling RocksDB's jumpy
+    // pending-compaction-bytes gauge, it moves incrementally so it's
+    // inherently smoother.
+    compaction_debt: f64,
+    last_pending_compaction_bytes: Option<u64>,
     long_term_pending_bytes: Smoother<f64, 60>,
 
     // On start related markers. Because after restart, the memtable, l0 files
@@ -393,12 +839,25 @@ struct CFFlowChecker {
     on_start_memtable: bool,
     on_start_l0_files: bool,
     on_start_pending_bytes: bool,
+
+    // Two-sided feedback related. Whether this CF was throttled on the
+    // previous tick, and whether it was within the feedback proximity of the
+    // stop thresholds, see `FlowChecker::apply_feedback`.
+    was_throttled_last_tick: bool,
+    near_stop_last_tick: bool,
+
+    // bLSM cross-level backpressure related: number of files at each level,
+    // used as a proxy for level size since per-level byte counters aren't
+    // exposed. See `FlowChecker::blsm_speed_limit`.
+    level_num_files: Vec<Smoother<u64, 30>>,
 }
 
 impl Default for CFFlowChecker {
     fn default() -> Self {
         Self {
             last_num_memtables: Smoother::default(),
+            compaction_debt: 0.0,
+            last_pending_compaction_bytes: None,
             long_term_pending_bytes: Smoother::default(),
             long_term_num_l0_files: Smoother::default(),
             last_num_l0_files: 0,
@@ -410,11 +869,15 @@ impl Default for CFFlowChecker {
             last_l0_bytes: 0,
             last_l0_bytes_time: Instant::now_coarse(),
             short_term_l0_consumption_flow: Smoother::default(),
+            long_term_l0_consumption_flow: Smoother::default(),
             memtable_debt: 0.0,
             init_speed: false,
             on_start_memtable: true,
             on_start_l0_files: true,
             on_start_pending_bytes: true,
+            was_throttled_last_tick: false,
+            near_stop_last_tick: false,
+            level_num_files: (0..MAX_LSM_LEVELS).map(|_| Smoother::default()).collect(),
         }
     }
 }
@@ -425,6 +888,31 @@ struct FlowChecker<E: KvEngine> {
     memtables_threshold: u64,
     l0_files_threshold: u64,
 
+    // Two-sided feedback gains and proximity window, see `apply_feedback`.
+    feedback_up_percent: f64,
+    feedback_down_percent: f64,
+    feedback_proximity_files: u64,
+
+    // Two-tier best-effort limit: `limit_low` is the guaranteed floor,
+    // `limit_max` the burst ceiling; `effective_limit_state` is the current
+    // point between them, see `effective_limit`. `limit_low == 0.0` disables
+    // the feature, matching the single hard-cap behavior.
+    limit_low: f64,
+    limit_max: f64,
+    effective_limit_state: f64,
+
+    // Online-reconfigurable tuning constants, see `reconfigure`.
+    min_throttle_speed: f64,
+    max_throttle_speed: f64,
+    ema_factor: f64,
+    limit_up_percent: f64,
+    limit_down_percent: f64,
+
+    // bLSM cross-level rate-matching mode, selectable alongside the L0/
+    // memtable logic above, see `blsm_speed_limit`.
+    blsm_mode_enabled: bool,
+    blsm_fanout: f64,
+
     // CFFlowChecker for each CF.
     cf_checkers: HashMap<String, CFFlowChecker>,
     // Record which CF is taking control of throttling, the throttle speed is
@@ -445,6 +933,16 @@ struct FlowChecker<E: KvEngine> {
     // Records the foreground write flow at scheduler level of last few seconds.
     write_flow_recorder: Smoother<u64, 30>,
     last_record_time: Instant,
+
+    // Which signal last moved the throttle speed, see `update_speed_limit`.
+    // Surfaced in the periodic diagnostics log so operators can tell which
+    // of memtable/L0/pending-compaction-bytes pressure is driving a given
+    // write slowdown.
+    last_throttle_cause: &'static str,
+
+    // Pluggable algorithm deciding the next throttle speed from the flush
+    // flow error, see `CongestionControl`.
+    congestion_control: Box<dyn CongestionControl>,
 }
 
 impl<E: KvEngine> FlowChecker<E> {
@@ -465,6 +963,19 @@ impl<E: KvEngine> FlowChecker<E> {
             hard_pending_compaction_bytes_limit: config.hard_pending_compaction_bytes_limit.0,
             memtables_threshold: config.memtables_threshold,
             l0_files_threshold: config.l0_files_threshold,
+            feedback_up_percent: config.feedback_up_percent,
+            feedback_down_percent: config.feedback_down_percent,
+            feedback_proximity_files: config.feedback_proximity_files,
+            limit_low: config.io_limit_low.0 as f64,
+            limit_max: config.io_limit_max.0 as f64,
+            effective_limit_state: config.io_limit_max.0 as f64,
+            min_throttle_speed: config.min_throttle_speed.0 as f64,
+            max_throttle_speed: config.max_throttle_speed.0 as f64,
+            ema_factor: config.ema_factor,
+            limit_up_percent: config.limit_up_percent,
+            limit_down_percent: config.limit_down_percent,
+            blsm_mode_enabled: config.blsm_mode_enabled,
+            blsm_fanout: config.blsm_fanout,
             engine,
             discard_ratio,
             limiter,
@@ -474,6 +985,8 @@ impl<E: KvEngine> FlowChecker<E> {
             l0_target_flow: 0.0,
             num_l0_for_last_update_target_flow: None,
             last_record_time: Instant::now_coarse(),
+            last_throttle_cause: "none",
+            congestion_control: new_congestion_control(config),
         }
     }
 
@@ -485,6 +998,7 @@ impl<E: KvEngine> FlowChecker<E> {
                 let mut checker = self;
                 let mut deadline = std::time::Instant::now();
                 let mut spare_ticks = 0;
+                let mut diagnostics_ticks = 0;
                 let mut enabled = true;
                 loop {
                     match rx.try_recv() {
@@ -496,6 +1010,9 @@ impl<E: KvEngine> FlowChecker<E> {
                         Ok(Msg::Enable) => {
                             enabled = true;
                         }
+                        Ok(Msg::Reconfigure(new_config)) => {
+                            checker.reconfigure(&new_config);
+                        }
                         Err(_) => {}
                     }
 
@@ -550,6 +1067,11 @@ impl<E: KvEngine> FlowChecker<E> {
                                 spare_ticks = 0;
                             }
                             checker.update_statistics();
+                            diagnostics_ticks += 1;
+                            if diagnostics_ticks == DIAGNOSTICS_LOG_TICKS {
+                                checker.log_diagnostics();
+                                diagnostics_ticks = 0;
+                            }
                             deadline = std::time::Instant::now() + SPARE_TICK_DURATION;
                         }
                         Err(e) => {
@@ -562,6 +1084,46 @@ impl<E: KvEngine> FlowChecker<E> {
             .unwrap()
     }
 
+    // Applies a new `FlowControlConfig` live: validates the new bounds,
+    // clamps the current speed limit to them immediately, rebuilds the
+    // congestion-control algorithm (it may have changed), and refreshes the
+    // other tuning constants. Called from `Msg::Reconfigure`.
+    fn reconfigure(&mut self, config: &FlowControlConfig) {
+        if config.min_throttle_speed.0 > config.max_throttle_speed.0 {
+            warn!(
+                "invalid flow control reconfigure: min throttle speed is greater than max";
+                "min" => config.min_throttle_speed.0,
+                "max" => config.max_throttle_speed.0,
+            );
+            return;
+        }
+
+        self.soft_pending_compaction_bytes_limit = config.soft_pending_compaction_bytes_limit.0;
+        self.hard_pending_compaction_bytes_limit = config.hard_pending_compaction_bytes_limit.0;
+        self.memtables_threshold = config.memtables_threshold;
+        self.l0_files_threshold = config.l0_files_threshold;
+        self.feedback_up_percent = config.feedback_up_percent;
+        self.feedback_down_percent = config.feedback_down_percent;
+        self.feedback_proximity_files = config.feedback_proximity_files;
+        self.limit_low = config.io_limit_low.0 as f64;
+        self.limit_max = config.io_limit_max.0 as f64;
+        self.min_throttle_speed = config.min_throttle_speed.0 as f64;
+        self.max_throttle_speed = config.max_throttle_speed.0 as f64;
+        self.ema_factor = config.ema_factor;
+        self.limit_up_percent = config.limit_up_percent;
+        self.limit_down_percent = config.limit_down_percent;
+        self.blsm_mode_enabled = config.blsm_mode_enabled;
+        self.blsm_fanout = config.blsm_fanout;
+        self.congestion_control = new_congestion_control(config);
+
+        let current = self.limiter.speed_limit();
+        if current != INFINITY {
+            self.limiter.set_speed_limit(
+                current.max(self.min_throttle_speed).min(self.max_throttle_speed),
+            );
+        }
+    }
+
     fn reset_statistics(&mut self) {
         SCHED_L0_TARGET_FLOW_GAUGE.set(0);
         for cf in self.cf_checkers.keys() {
@@ -586,6 +1148,7 @@ impl<E: KvEngine> FlowChecker<E> {
         self.limiter.set_speed_limit(INFINITY);
         SCHED_DISCARD_RATIO_GAUGE.set(0);
         self.discard_ratio.store(0, Ordering::Relaxed);
+        self.congestion_control.reset();
     }
 
     fn update_statistics(&mut self) {
@@ -627,19 +1190,28 @@ impl<E: KvEngine> FlowChecker<E> {
     }
 
     fn on_pending_compaction_bytes_change(&mut self, cf: String) {
-        let hard = (self.hard_pending_compaction_bytes_limit as f64).log2();
-        let soft = (self.soft_pending_compaction_bytes_limit as f64).log2();
-
-        // Because pending compaction bytes changes dramatically, take the
-        // logarithm of pending compaction bytes to make the values fall into
-        // a relative small range
-        let num = (self
+        let soft = self.soft_pending_compaction_bytes_limit as f64;
+        let hard = self.hard_pending_compaction_bytes_limit as f64;
+
+        // FlowInfo::Compaction carries no byte count, unlike Flush/L0/L0Intra
+        // which update `compaction_debt` directly in `on_l0_incr`/`on_l0_decr`.
+        // So for this (non-L0) compaction, approximate the drained bytes from
+        // the drop in RocksDB's own pending-compaction-bytes estimate.
+        let pending_now = self
             .engine
             .get_cf_pending_compaction_bytes(&cf)
             .unwrap_or(None)
-            .unwrap_or(0) as f64)
-            .log2();
+            .unwrap_or(0);
         let checker = self.cf_checkers.get_mut(&cf).unwrap();
+        if let Some(last) = checker.last_pending_compaction_bytes {
+            if pending_now < last {
+                checker.compaction_debt =
+                    (checker.compaction_debt - (last - pending_now) as f64).max(0.0);
+            }
+        }
+        checker.last_pending_compaction_bytes = Some(pending_now);
+
+        let num = checker.compaction_debt;
         checker.long_term_pending_bytes.observe(num);
         SCHED_PENDING_COMPACTION_BYTES_GAUGE
             .with_label_values(&[&cf])
@@ -656,7 +1228,7 @@ impl<E: KvEngine> FlowChecker<E> {
             }
         }
 
-        let pending_compaction_bytes = checker.long_term_pending_bytes.get_avg();
+        let compaction_debt = checker.long_term_pending_bytes.get_avg();
 
         for checker in self.cf_checkers.values() {
             if num < checker.long_term_pending_bytes.get_recent() {
@@ -664,17 +1236,30 @@ impl<E: KvEngine> FlowChecker<E> {
             }
         }
 
-        let ratio = if pending_compaction_bytes < soft {
+        let ratio = if compaction_debt < soft {
             0
         } else {
-            let new_ratio = (pending_compaction_bytes - soft) / (hard - soft);
+            // Map the debt linearly from soft to hard onto 0%-100%, instead
+            // of the jumpy raw pending-compaction-bytes gauge.
+            let new_ratio = ((compaction_debt - soft) / (hard - soft)).min(1.0);
             let old_ratio = self.discard_ratio.load(Ordering::Relaxed);
 
-            // Because pending compaction bytes changes up and down, so using
-            // EMA(Exponential Moving Average) to smooth it.
+            // The raw pending-compaction-bytes estimate is noisy; lean harder
+            // on history (a higher effective EMA factor) when the coefficient
+            // of variation of the recent window is high, so a one-off spike
+            // doesn't whipsaw the discard ratio. `self.ema_factor` remains
+            // the floor for a stable signal.
+            let cv = if compaction_debt > 0.0 {
+                self.cf_checkers[&cf].long_term_pending_bytes.get_stddev() / compaction_debt
+            } else {
+                0.0
+            };
+            let effective_ema_factor = (self.ema_factor + cv.min(1.0) * (1.0 - self.ema_factor)).min(0.99);
+
+            // Smooth further with EMA(Exponential Moving Average).
             (if old_ratio != 0 {
-                EMA_FACTOR * (old_ratio as f64 / RATIO_SCALE_FACTOR)
-                    + (1.0 - EMA_FACTOR) * new_ratio
+                effective_ema_factor * (old_ratio as f64 / RATIO_SCALE_FACTOR)
+                    + (1.0 - effective_ema_factor) * new_ratio
             } else if new_ratio > 0.01 {
                 0.01
             } else {
@@ -683,6 +1268,78 @@ impl<E: KvEngine> FlowChecker<E> {
         };
         SCHED_DISCARD_RATIO_GAUGE.set(ratio as i64);
         self.discard_ratio.store(ratio, Ordering::Relaxed);
+
+        if self.blsm_mode_enabled {
+            let throttle = self.blsm_speed_limit(&cf);
+            if throttle != INFINITY {
+                self.throttle_cf = Some(cf.clone());
+            }
+            self.update_speed_limit(throttle, "pending_compaction_bytes");
+        }
+    }
+
+    // bLSM merge-manager invariant: for every byte pushed into L0, roughly
+    // one byte must be drained by the downstream merge, extended across all
+    // levels. For every level, `target[i+1] = blsm_fanout * target[i]`
+    // (starting from the L0 files threshold as `target[0]`), and a byte
+    // drained `depth - i` levels below the top "pays for" `blsm_fanout^k`
+    // bytes of ingestion above it. The write speed is capped at the
+    // minimum of these scaled drain rates, with an extra proportional
+    // slowdown once any level's fill_ratio (current/target size) exceeds
+    // 1.0. Per-level byte counters aren't exposed by the engine, so file
+    // count is used as the size proxy.
+    //
+    // Returns `INFINITY` (no throttle) when every level's fill_ratio < 1,
+    // matching the existing L0/memtable mode's behavior of only throttling
+    // once a level is actually over its target.
+    fn blsm_speed_limit(&mut self, cf: &str) -> f64 {
+        let target0 = (self.l0_files_threshold.max(1)) as f64;
+        // rough average file size, used to turn a file drain rate into a
+        // byte drain rate.
+        let avg_file_bytes = {
+            let flow = self.cf_checkers[cf].long_term_l0_production_flow.get_avg();
+            if flow > 0.0 { flow / target0 } else { 1.0 }
+        };
+
+        let mut target = target0;
+        let mut max_fill_ratio = 0.0_f64;
+        let mut min_scaled_consume_flow = INFINITY;
+        for level in 0..MAX_LSM_LEVELS {
+            let num_files = self
+                .engine
+                .get_cf_num_files_at_level(cf, level as i32)
+                .unwrap_or(None)
+                .unwrap_or(0);
+            let checker = self.cf_checkers.get_mut(cf).unwrap();
+            checker.level_num_files[level].observe(num_files);
+
+            let fill_ratio = num_files as f64 / target;
+            max_fill_ratio = max_fill_ratio.max(fill_ratio);
+
+            let drain_files_per_sec = (-checker.level_num_files[level].slope()).max(0.0);
+            let scaled_consume_flow =
+                drain_files_per_sec * avg_file_bytes * self.blsm_fanout.powi(level as i32);
+            if scaled_consume_flow > 0.0 {
+                min_scaled_consume_flow = min_scaled_consume_flow.min(scaled_consume_flow);
+            }
+
+            target *= self.blsm_fanout;
+        }
+
+        if max_fill_ratio < 1.0 {
+            return INFINITY;
+        }
+        if min_scaled_consume_flow == INFINITY {
+            // Overfull (`max_fill_ratio >= 1.0`) with every level's slope <= 0
+            // -- nothing is draining at all. The invariant this function
+            // upholds is "never throttle above the bottleneck level's drain
+            // rate", and a drain rate of zero means the bottleneck can't
+            // absorb any more writes, so throttle as hard as the configured
+            // floor allows rather than falling through to "no throttle".
+            return self.min_throttle_speed;
+        }
+        // delay grows with how far over target the worst level is.
+        (min_scaled_consume_flow / max_fill_ratio).max(0.0)
     }
 
     fn on_memtable_decrs(&mut self, cf: &str) {
@@ -761,7 +1418,7 @@ impl<E: KvEngine> FlowChecker<E> {
             self.limiter.speed_limit() + diff * 1024.0 * 1024.0
         };
 
-        self.update_speed_limit(throttle);
+        self.update_speed_limit(throttle, "memtable");
     }
 
     fn tick_l0(&mut self) {
@@ -787,10 +1444,10 @@ impl<E: KvEngine> FlowChecker<E> {
                     SCHED_THROTTLE_ACTION_COUNTER
                         .with_label_values(&[cf, "up_spare"])
                         .inc();
-                    self.limiter.speed_limit() * (1.0 + 5.0 * LIMIT_UP_PERCENT)
+                    self.limiter.speed_limit() * (1.0 + 5.0 * self.limit_up_percent)
                 };
 
-                self.update_speed_limit(throttle)
+                self.update_speed_limit(throttle, "l0_files")
             }
         }
     }
@@ -804,6 +1461,10 @@ impl<E: KvEngine> FlowChecker<E> {
             .unwrap_or(0);
         let checker = self.cf_checkers.get_mut(&cf).unwrap();
         checker.last_l0_bytes += l0_bytes;
+        // L0 (and L0-intra) compactions drain L0 production flow; count them
+        // into compaction_debt immediately, without gating behind an L0-file
+        // threshold, so the debt signal doesn't jump between 0 and full.
+        checker.compaction_debt = (checker.compaction_debt - l0_bytes as f64).max(0.0);
         checker.long_term_num_l0_files.observe(num_l0_files);
         checker.last_num_l0_files = num_l0_files;
         SCHED_L0_GAUGE
@@ -847,6 +1508,9 @@ impl<E: KvEngine> FlowChecker<E> {
                     self.l0_target_flow = self.cf_checkers[&cf]
                         .short_term_l0_production_flow
                         .get_avg();
+                    // the accumulated integral term (if any) was computed
+                    // against the old CF's flow error, so it's stale now.
+                    self.congestion_control.reset();
                 } else {
                     return;
                 }
@@ -910,22 +1574,111 @@ impl<E: KvEngine> FlowChecker<E> {
                 SCHED_THROTTLE_ACTION_COUNTER
                     .with_label_values(&[&cf, "up"])
                     .inc();
-                self.limiter.speed_limit() * (1.0 + LIMIT_UP_PERCENT)
+                self.limiter.speed_limit() * (1.0 + self.limit_up_percent)
             }
         } else {
             INFINITY
         };
 
-        self.update_speed_limit(throttle)
+        self.update_speed_limit(throttle, "l0_files");
+        self.apply_feedback(&cf, num_l0_files);
     }
 
-    fn update_speed_limit(&mut self, mut throttle: f64) {
-        if throttle < MIN_THROTTLE_SPEED {
-            throttle = MIN_THROTTLE_SPEED;
+    // Two-sided feedback so the throttle speed converges instead of
+    // monotonically ratcheting down under sustained heavy write:
+    //   * positive feedback fires on the throttled->recovered transition,
+    //     raising l0_target_flow by `feedback_up_percent`;
+    //   * negative feedback fires when the CF creeps within
+    //     `feedback_proximity_files` of `l0_files_threshold`/
+    //     `memtables_threshold`, lowering l0_target_flow by
+    //     `feedback_down_percent`, ahead of actually crossing the threshold.
+    // Firing on proximity rather than only on the threshold crossing is what
+    // makes the value oscillate around a balance point instead of decaying.
+    fn apply_feedback(&mut self, cf: &str, num_l0_files: u64) {
+        let is_throttled = self.limiter.speed_limit() != INFINITY;
+        let num_memtables = self.cf_checkers[cf].last_num_memtables.get_recent();
+
+        let recovered = self.cf_checkers[cf].was_throttled_last_tick
+            && !is_throttled
+            && num_l0_files < self.l0_files_threshold;
+        if recovered {
+            self.l0_target_flow *= 1.0 + self.feedback_up_percent;
+            SCHED_POSITIVE_FEEDBACK_COUNTER.with_label_values(&[cf]).inc();
+        }
+
+        let near_stop = num_l0_files + self.feedback_proximity_files >= self.l0_files_threshold
+            || num_memtables + self.feedback_proximity_files >= self.memtables_threshold;
+        if near_stop && !self.cf_checkers[cf].near_stop_last_tick {
+            self.l0_target_flow *= 1.0 - self.feedback_down_percent;
+            SCHED_NEGATIVE_FEEDBACK_COUNTER.with_label_values(&[cf]).inc();
+        }
+        SCHED_L0_TARGET_FLOW_GAUGE.set(self.l0_target_flow as i64);
+
+        let checker = self.cf_checkers.get_mut(cf).unwrap();
+        checker.was_throttled_last_tick = is_throttled;
+        checker.near_stop_last_tick = near_stop;
+    }
+
+    // Computes the current point of the two-tier best-effort limit between
+    // `limit_low` (always honored) and `limit_max` (may be reached when the
+    // store isn't under pressure). The point is nudged, rather than jumped,
+    // toward its target so a mostly-idle store can burst up to `limit_max`
+    // while a store approaching its thresholds is pulled back down to
+    // `limit_low`.
+    fn effective_limit(&mut self, cf: &str) -> f64 {
+        if self.limit_low <= 0.0 {
+            // two-tier limiting disabled, behave like the single hard cap.
+            return self.limit_max;
+        }
+        let checker = &self.cf_checkers[cf];
+        let under_pressure = checker.long_term_num_l0_files.trend() == Trend::Increasing
+            || checker.long_term_pending_bytes.trend() == Trend::Increasing;
+        let has_headroom = checker.long_term_l0_consumption_flow.trend() == Trend::Decreasing
+            || (!under_pressure
+                && checker.long_term_l0_consumption_flow.get_avg()
+                    > checker.long_term_l0_production_flow.get_avg());
+        self.effective_limit_state = if under_pressure {
+            (self.effective_limit_state * (1.0 - self.limit_down_percent)).max(self.limit_low)
+        } else if has_headroom {
+            (self.effective_limit_state * (1.0 + self.limit_up_percent)).min(self.limit_max)
+        } else {
+            self.effective_limit_state
+        };
+        SCHED_EFFECTIVE_LIMIT_GAUGE
+            .with_label_values(&[cf])
+            .set(self.effective_limit_state as i64);
+        self.effective_limit_state
+    }
+
+    // `cause` attributes this throttle decision to the signal that drove it
+    // (e.g. "memtable", "l0_files", "pending_compaction_bytes"), recorded in
+    // `last_throttle_cause` for the periodic diagnostics log; see
+    // `log_diagnostics`.
+    fn update_speed_limit(&mut self, mut throttle: f64, cause: &'static str) {
+        // `throttle == INFINITY` means the control loop decided to release
+        // throttling entirely; clamping it to `ceiling` (<= max_throttle_speed)
+        // below would make the `throttle > self.max_throttle_speed` escape
+        // hatch further down never fire, pinning `throttle_cf` at `ceiling`
+        // forever. Compare against the effective limit only when there's an
+        // actual finite speed to clamp.
+        if throttle != INFINITY {
+            if let Some(cf) = self.throttle_cf.clone() {
+                let ceiling = self.effective_limit(&cf);
+                if ceiling > 0.0 && throttle > ceiling {
+                    throttle = ceiling;
+                }
+                if self.limit_low > 0.0 && throttle < self.limit_low {
+                    throttle = self.limit_low;
+                }
+            }
         }
-        if throttle > MAX_THROTTLE_SPEED {
+        if throttle < self.min_throttle_speed {
+            throttle = self.min_throttle_speed;
+        }
+        if throttle > self.max_throttle_speed {
             self.throttle_cf = None;
             self.num_l0_for_last_update_target_flow = None;
+            self.congestion_control.reset();
             throttle = INFINITY;
         }
         SCHED_THROTTLE_FLOW_GAUGE.set(if throttle == INFINITY {
@@ -933,6 +1686,7 @@ impl<E: KvEngine> FlowChecker<E> {
         } else {
             throttle as i64
         });
+        self.last_throttle_cause = if throttle == INFINITY { "none" } else { cause };
         self.limiter.set_speed_limit(throttle)
     }
 
@@ -946,6 +1700,8 @@ impl<E: KvEngine> FlowChecker<E> {
 
         let checker = self.cf_checkers.get_mut(&cf).unwrap();
         checker.last_flush_bytes += flush_bytes;
+        // every flush adds to the bytes that must eventually be compacted.
+        checker.compaction_debt += flush_bytes as f64;
         // no need to add it to long_term_num_l0_files which only records result right after L0 compaction.
         checker.last_num_l0_files = num_l0_files;
         checker.last_num_l0_files_from_flush = num_l0_files;
@@ -978,6 +1734,9 @@ impl<E: KvEngine> FlowChecker<E> {
                 checker
                     .short_term_l0_consumption_flow
                     .observe(l0_flow as u64);
+                checker
+                    .long_term_l0_consumption_flow
+                    .observe(l0_flow as u64);
                 SCHED_L0_FLOW_GAUGE
                     .with_label_values(&[&cf])
                     .set(checker.short_term_l0_consumption_flow.get_avg() as i64);
@@ -1019,7 +1778,7 @@ impl<E: KvEngine> FlowChecker<E> {
                     SCHED_THROTTLE_ACTION_COUNTER
                         .with_label_values(&[&cf, "down_flow"])
                         .inc();
-                    self.decrease_speed_limit(cf);
+                    self.adjust_speed_limit(cf);
                 } else if (self.cf_checkers[&cf]
                     .short_term_l0_production_flow
                     .get_avg()
@@ -1034,7 +1793,7 @@ impl<E: KvEngine> FlowChecker<E> {
                     SCHED_THROTTLE_ACTION_COUNTER
                         .with_label_values(&[&cf, "up_flow"])
                         .inc();
-                    self.increase_speed_limit(cf);
+                    self.adjust_speed_limit(cf);
                 } else {
                     SCHED_THROTTLE_ACTION_COUNTER
                         .with_label_values(&[&cf, "keep_flow"])
@@ -1048,41 +1807,55 @@ impl<E: KvEngine> FlowChecker<E> {
         }
     }
 
-    fn increase_speed_limit(&mut self, cf: String) {
+    // Adjusts the throttle speed by delegating the flush-flow-vs-target-flow
+    // decision to `congestion_control`, so the algorithm (PID, AIMD, ...) can
+    // be swapped without touching the rest of the control loop.
+    fn adjust_speed_limit(&mut self, cf: String) {
         let throttle = if self.limiter.speed_limit() == INFINITY {
             self.throttle_cf = Some(cf);
             let x = self.write_flow_recorder.get_percentile_90();
             if x == 0 { INFINITY } else { x as f64 }
         } else {
-            // Use PID algorithm to change the flow so up flow can be increased
-            // rapidly when the target flow is quite larger than flush flow.
-            let mut u = PID_KP_FACTOR
-                * (self.l0_target_flow
-                    - self.cf_checkers[&cf]
-                        .short_term_l0_production_flow
-                        .get_avg()
-                    + PID_KD_FACTOR * -self.cf_checkers[&cf].short_term_l0_production_flow.slope());
-            if u > self.limiter.speed_limit() {
-                u = self.limiter.speed_limit();
-            } else if u < 0.0 {
-                u = 0.0;
-            };
-            SCHED_UP_FLOW_GAUGE.set((u * RATIO_SCALE_FACTOR) as i64);
-
-            self.limiter.speed_limit() + u
+            let checker = &self.cf_checkers[&cf];
+            let recent_flow = checker.short_term_l0_production_flow.get_avg();
+            let trend = checker.short_term_l0_production_flow.trend();
+            let slope = checker.short_term_l0_production_flow.slope();
+            let next = self.congestion_control.next_speed(
+                self.limiter.speed_limit(),
+                recent_flow,
+                self.l0_target_flow,
+                &trend,
+                slope,
+            );
+            let delta = next - self.limiter.speed_limit();
+            if delta > 0.0 {
+                SCHED_UP_FLOW_GAUGE.set((delta * RATIO_SCALE_FACTOR) as i64);
+            } else {
+                SCHED_DOWN_FLOW_GAUGE.set((-delta * RATIO_SCALE_FACTOR) as i64);
+            }
+            next
         };
-        self.update_speed_limit(throttle)
+        self.update_speed_limit(throttle, "l0_files")
     }
 
-    fn decrease_speed_limit(&mut self, cf: String) {
-        let throttle = if self.limiter.speed_limit() == INFINITY {
-            self.throttle_cf = Some(cf);
-            let x = self.write_flow_recorder.get_percentile_90();
-            if x == 0 { INFINITY } else { x as f64 }
-        } else {
-            self.limiter.speed_limit() * (1.0 - LIMIT_DOWN_PERCENT)
-        };
-        self.update_speed_limit(throttle)
+    // Emits an aggregated, human-readable summary of the flow-control state,
+    // called every `DIAGNOSTICS_LOG_TICKS` from the checker thread's timeout
+    // arm. Unlike the per-tick gauges, this is meant to be skimmed in logs to
+    // answer "is a write slowdown flow-control-induced, and by what" without
+    // a metrics dashboard.
+    fn log_diagnostics(&self) {
+        if self.throttle_cf.is_none() && self.discard_ratio.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        info!(
+            "flow control diagnostics";
+            "throttle_cf" => ?self.throttle_cf,
+            "throttle_cause" => self.last_throttle_cause,
+            "current_speed_limit" => self.limiter.speed_limit(),
+            "l0_target_flow" => self.l0_target_flow,
+            "observed_write_flow" => self.write_flow_recorder.get_avg(),
+            "discard_ratio" => self.discard_ratio.load(Ordering::Relaxed) as f64 / RATIO_SCALE_FACTOR,
+        );
     }
 }
 
@@ -1123,4 +1896,18 @@ mod tests {
         assert_eq!(smoother.get_percentile_90(), 5.0);
         assert_eq!(smoother.trend(), Trend::Increasing);
     }
+
+    #[test]
+    fn test_smoother_stats() {
+        let mut smoother = Smoother::<u64, 5>::default();
+        // window ends up holding the last 5 of these: [2, 3, 4, 5, 0]
+        for v in [1, 6, 2, 3, 4, 5, 0] {
+            smoother.observe(v);
+        }
+
+        assert_eq!(smoother.get_percentile(0.90), smoother.get_percentile_90());
+        assert_eq!(smoother.get_trimmed_mean(0.2), 3.0); // drops 0 and 5, mean of [2, 3, 4]
+        assert!((smoother.get_variance() - 2.96).abs() < 1e-9);
+        assert!((smoother.get_stddev() - 2.96_f64.sqrt()).abs() < 1e-9);
+    }
 }
@@ -83,6 +83,75 @@ fn test_disk_full_for_region_leader() {
     test_disk_full_leader_behaviors(DiskUsage::AlreadyFull);
 }
 
+// Under `AlreadyFull` the generic proposal rejection would also block the
+// very operations that free space, which can deadlock recovery. Proposals
+// recognized as "reclaim" (DeleteRange/unsafe-destroy-range and raft-log GC) are
+// a distinct class the disk-full gate must let through even in
+// `AlreadyFull`, while ordinary writes stay rejected.
+#[test]
+fn test_disk_full_allows_reclaim_proposals() {
+    let mut cluster = new_server_cluster(0, 3);
+    cluster.pd_client.disable_default_operator();
+    cluster.run();
+
+    for i in 0..10 {
+        cluster.must_put(format!("k{:02}", i).as_bytes(), b"v");
+    }
+    must_get_equal(&cluster.get_engine(1), b"k00", b"v");
+    must_get_equal(&cluster.get_engine(2), b"k00", b"v");
+    must_get_equal(&cluster.get_engine(3), b"k00", b"v");
+
+    cluster.must_transfer_leader(1, new_peer(1, 1));
+    fail::cfg(get_fp(DiskUsage::AlreadyFull, 1), "return").unwrap();
+
+    // Plain writes are still rejected.
+    let rx = cluster.async_put(b"k99", b"v").unwrap();
+    assert_disk_full(&rx.recv_timeout(Duration::from_secs(2)).unwrap());
+
+    // A DeleteRange, recognized as a reclaim proposal, is accepted and
+    // removes the keys in [k00, k05).
+    let region = cluster.get_region(b"k00");
+    let mut delete_range_req = Request::default();
+    delete_range_req.set_cmd_type(CmdType::DeleteRange);
+    delete_range_req
+        .mut_delete_range()
+        .set_start_key(b"k00".to_vec());
+    delete_range_req
+        .mut_delete_range()
+        .set_end_key(b"k05".to_vec());
+    // Deliberately left at the default `DiskFullOpt` (`NotAllowedOnFull`): the
+    // point of this case is that the gate recognizes a `DeleteRange` as a
+    // reclaim proposal on its own, not that the client asked nicely.
+    let request = new_request(
+        region.get_id(),
+        region.get_region_epoch().clone(),
+        vec![delete_range_req],
+        false,
+    );
+    let resp = cluster
+        .call_command_on_leader(request, Duration::from_secs(3))
+        .unwrap();
+    assert!(!resp.get_header().get_error().has_disk_full(), "{:?}", resp);
+    must_get_none(&cluster.get_engine(1), b"k00");
+    must_get_equal(&cluster.get_engine(1), b"k05", b"v");
+
+    // A raft-log GC (CompactLog) admin proposal is likewise accepted.
+    // Same here: no `AllowedOnAlreadyFull`, so this only passes if
+    // `is_reclaim_proposal` recognizes `CompactLog` by itself.
+    let state = cluster.raft_local_state(1, 1);
+    let compact_log = new_admin_request(
+        region.get_id(),
+        region.get_region_epoch(),
+        new_compact_log_request(state.last_index, state.get_hard_state().get_term()),
+    );
+    let resp = cluster
+        .call_command_on_leader(compact_log, Duration::from_secs(3))
+        .unwrap();
+    assert!(!resp.get_header().get_error().has_disk_full(), "{:?}", resp);
+
+    fail::remove(get_fp(DiskUsage::AlreadyFull, 1));
+}
+
 fn test_disk_full_follower_behaviors(usage: DiskUsage) {
     let mut cluster = new_server_cluster(0, 3);
     cluster.pd_client.disable_default_operator();
@@ -113,6 +182,30 @@ fn test_disk_full_follower_behaviors(usage: DiskUsage) {
     assert_eq!(old_last_index, new_last_index);
     must_get_none(&cluster.get_engine(2), b"k3");
 
+    // Test followers still serve replica reads via ReadIndex when disk full:
+    // a replica read consumes no disk space, so the disk-usage check must
+    // not short-circuit ReadIndex handling the way it does for writes.
+    let pd_client = cluster.pd_client.clone();
+    let read_ts = get_tso(&pd_client);
+    let mut read_index_req = new_read_index_cmd();
+    read_index_req.mut_read_index().set_start_ts(read_ts);
+    let region = cluster.get_region(b"k1");
+    let request = new_request(
+        region.get_id(),
+        region.get_region_epoch().clone(),
+        vec![read_index_req],
+        true, // replica read
+    );
+    let resp = cluster
+        .call_command_on_peer(request, new_peer(2, 2), Duration::from_secs(3))
+        .unwrap();
+    assert!(
+        !resp.get_header().get_error().has_disk_full(),
+        "replica read should not be rejected by the disk-full gate: {:?}",
+        resp
+    );
+    must_get_equal(&cluster.get_engine(2), b"k2", b"v2");
+
     // Test followers will response votes when disk is full.
     cluster.add_send_filter(CloneFilterFactory(
         RegionPacketFilter::new(1, 1)
@@ -228,3 +321,152 @@ fn test_disk_full_txn_behaviors(usage: DiskUsage) {
 fn test_disk_full_for_txn_operations() {
     test_disk_full_txn_behaviors(DiskUsage::AlmostFull);
 }
+
+// ReadIndex is read-only and appends no raft log entry, so the disk-full
+// proposal gate must special-case it instead of folding it into the generic
+// write rejection: otherwise a leader that is `AlreadyFull` would stop
+// serving snapshot reads, and worse, would skip bumping the concurrency
+// manager's `max_ts`, letting a later async-commit prewrite pick a
+// `min_commit_ts` below an already-returned read.
+#[test]
+fn test_disk_full_read_index_advances_max_ts() {
+    let mut cluster = new_server_cluster(0, 3);
+    cluster.pd_client.disable_default_operator();
+    cluster.run();
+
+    cluster.must_put(b"k1", b"v1");
+    must_get_equal(&cluster.get_engine(1), b"k1", b"v1");
+    must_get_equal(&cluster.get_engine(2), b"k1", b"v1");
+    must_get_equal(&cluster.get_engine(3), b"k1", b"v1");
+
+    cluster.must_transfer_leader(1, new_peer(1, 1));
+    fail::cfg(get_fp(DiskUsage::AlreadyFull, 1), "return").unwrap();
+
+    let pd_client = cluster.pd_client.clone();
+    let read_ts = get_tso(&pd_client);
+    let region = cluster.get_region(b"k1");
+    let mut read_index_req = new_read_index_cmd();
+    read_index_req
+        .mut_read_index()
+        .set_start_ts(read_ts);
+    let request = new_request(
+        region.get_id(),
+        region.get_region_epoch().clone(),
+        vec![read_index_req],
+        false,
+    );
+    let resp = cluster
+        .call_command_on_leader(request, Duration::from_secs(3))
+        .unwrap();
+    assert!(
+        !resp.get_header().get_error().has_disk_full(),
+        "ReadIndex should not be rejected by the disk-full gate: {:?}",
+        resp
+    );
+
+    let max_ts = cluster
+        .sim
+        .rl()
+        .get_concurrency_manager(1)
+        .max_ts()
+        .into_inner();
+    assert!(
+        max_ts >= read_ts,
+        "max_ts ({}) should have been advanced to at least the ReadIndex ts ({})",
+        max_ts,
+        read_ts
+    );
+
+    // A subsequent allowed-on-full prewrite must compute a min_commit_ts
+    // strictly above the already-served read.
+    let lead_client = PeerClient::new(&cluster, 1, new_peer(1, 1));
+    let prewrite_ts = get_tso(&pd_client);
+    let res = lead_client.try_kv_prewrite(
+        vec![new_mutation(Op::Put, b"k9", b"v9")],
+        b"k9".to_vec(),
+        prewrite_ts,
+        DiskFullOpt::AllowedOnAlreadyFull,
+    );
+    assert!(!res.get_region_error().has_disk_full());
+    assert!(res.get_min_commit_ts() > read_ts);
+
+    fail::remove(get_fp(DiskUsage::AlreadyFull, 1));
+}
+
+// A peer that is both disk-full and mid-way through applying a received
+// snapshot (region state `Applying`) must not drop an incoming ReadIndex:
+// the disk-full gate and the applying-state gate are two independent reasons
+// a peer might defer a command, and they must compose rather than the first
+// one to run rejecting it outright. Modeled on `test_disk_full_follower_behaviors`,
+// but additionally isolates the follower long enough that it falls behind
+// and has to receive a snapshot, then fills its disk and pauses the apply
+// of that snapshot before issuing the ReadIndex.
+fn test_disk_full_follower_applying_behaviors(usage: DiskUsage) {
+    let mut cluster = new_server_cluster(0, 3);
+    cluster.pd_client.disable_default_operator();
+    cluster.run();
+
+    cluster.must_put(b"k1", b"v1");
+    must_get_equal(&cluster.get_engine(1), b"k1", b"v1");
+    must_get_equal(&cluster.get_engine(2), b"k1", b"v1");
+    must_get_equal(&cluster.get_engine(3), b"k1", b"v1");
+
+    cluster.must_transfer_leader(1, new_peer(1, 1));
+
+    // Isolate store 2 so it falls far enough behind that the leader has to
+    // send it a snapshot instead of raft log entries.
+    cluster.add_send_filter(IsolationFilterFactory::new(2));
+    for i in 0..20 {
+        cluster.must_put(format!("k1{}", i).as_bytes(), b"v");
+    }
+
+    // Pause the snapshot apply on store 2 right before it finishes, and only
+    // then let the isolated messages (including the snapshot) through.
+    fail::cfg("before_handle_snapshot_ready", "pause").unwrap();
+    cluster.clear_send_filters();
+
+    fail::cfg(get_fp(usage, 2), "return").unwrap();
+
+    let pd_client = cluster.pd_client.clone();
+    let read_ts = get_tso(&pd_client);
+    let mut read_index_req = new_read_index_cmd();
+    read_index_req.mut_read_index().set_start_ts(read_ts);
+    let region = cluster.get_region(b"k1");
+    let request = new_request(
+        region.get_id(),
+        region.get_region_epoch().clone(),
+        vec![read_index_req],
+        true, // replica read
+    );
+    let (tx, rx) = mpsc::sync_channel(1);
+    cluster
+        .sim
+        .rl()
+        .async_command_on_node(2, request, Callback::read(Box::new(move |resp| {
+            tx.send(resp).unwrap();
+        })))
+        .unwrap();
+
+    // The ReadIndex must not be answered (let alone rejected) while still
+    // `Applying`; it should be queued instead.
+    assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+
+    fail::remove("before_handle_snapshot_ready");
+
+    // Once the snapshot finishes applying, the queued ReadIndex should be
+    // resolved without a disk-full error.
+    let resp = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(
+        !resp.get_header().get_error().has_disk_full(),
+        "{:?}",
+        resp
+    );
+
+    fail::remove(get_fp(usage, 2));
+}
+
+#[test]
+fn test_disk_full_for_region_follower_applying() {
+    test_disk_full_follower_applying_behaviors(DiskUsage::AlmostFull);
+    test_disk_full_follower_applying_behaviors(DiskUsage::AlreadyFull);
+}